@@ -18,6 +18,8 @@ from sbp.msg import SBP, SENDER_ID
 
 from threading import Event, Lock
 
+import logging
+
 from multiprocessing.pool import ThreadPool
 
 cpdef enum SettingsWriteResponseCodes:
@@ -30,6 +32,20 @@ cpdef enum SettingsWriteResponseCodes:
     SETTINGS_WR_SERVICE_FAILED = 6
     SETTINGS_WR_TIMEOUT = 7
 
+logger = logging.getLogger(__name__)
+
+# Map libswiftnav (syslog style) log priorities onto python logging levels
+_LOG_LEVELS = {
+    0: logging.CRITICAL,  # LOG_EMERG
+    1: logging.CRITICAL,  # LOG_ALERT
+    2: logging.CRITICAL,  # LOG_CRIT
+    3: logging.ERROR,     # LOG_ERROR
+    4: logging.WARNING,   # LOG_WARN
+    5: logging.INFO,      # LOG_NOTICE
+    6: logging.INFO,      # LOG_INFO
+    7: logging.DEBUG,     # LOG_DEBUG
+}
+
 cdef extern from "../include/libsettings/settings.h":
 
     cdef enum:
@@ -279,25 +295,26 @@ cdef class Settings:
         return ret
 
     def _callback_broker(self, sbp_msg, **metadata):
+        cdef sbp_msg_callback_t cb
+
         if self._debug:
             print '_callback_broker', sbp_msg.msg_type
             print ":".join("{:02x}".format(ord(c)) for c in sbp_msg.payload)
 
         msg_type = sbp_msg.msg_type
 
-        cb_data = None
+        # Snapshot the matching callbacks, the C callbacks may (un)register
+        # callbacks while being invoked
+        cb_list = [v for v in list(self._callbacks.values()) if v[0] == msg_type]
 
-        for k,v in self._callbacks.items():
-            # Only one callback per msg type
-            if v[0] == msg_type:
-                cb_data = self._callbacks[k]
-                break
-
-        if cb_data is None:
+        if not cb_list:
             raise Exception("Callback not registered for message type {}".format(msg_type))
 
-        cdef sbp_msg_callback_t cb = <sbp_msg_callback_t><uintptr_t>cb_data[1]
-        cb(sbp_msg.sender, sbp_msg.length, bytes(sbp_msg.payload), <void*><uintptr_t>cb_data[2])
+        payload = bytes(sbp_msg.payload)
+
+        for cb_data in cb_list:
+            cb = <sbp_msg_callback_t><uintptr_t>cb_data[1]
+            cb(sbp_msg.sender, sbp_msg.length, payload, <void*><uintptr_t>cb_data[2])
 
 cdef int send_wrapper(void *ctx, uint16_t msg_type, uint8_t length, uint8_t *payload):
     settings = <object>ctx
@@ -382,9 +399,13 @@ cdef int register_cb_wrapper(void *ctx,
 cdef int unregister_cb_wrapper(void *ctx, sbp_msg_callbacks_node_t **node):
     try:
         settings = <object>ctx
-        cb_data = settings._callbacks[<uintptr_t>node]
-        settings._link.remove_callback(settings._callback_broker, cb_data[0])
-        del settings._callbacks[<uintptr_t>node]
+        cb_data = settings._callbacks.pop(<uintptr_t>node)
+        # Broker is shared by all callbacks of the same message type
+        for v in settings._callbacks.values():
+            if v[0] == cb_data[0]:
+                break
+        else:
+            settings._link.remove_callback(settings._callback_broker, cb_data[0])
     except:
         return -1
 
@@ -393,4 +414,6 @@ cdef int unregister_cb_wrapper(void *ctx, sbp_msg_callbacks_node_t **node):
 cdef void log_wrapper(int priority, const char *fmt, ...):
     # Currently no proper way to cythonize the variadic arguments..
     # https://github.com/cython/cython/wiki/FAQ#how-do-i-use-variable-args
-    print fmt
+    # log_preformat is set so fmt already contains the formatted message.
+    logger.log(_LOG_LEVELS.get(priority, logging.INFO),
+               fmt.decode('ascii', 'replace'))